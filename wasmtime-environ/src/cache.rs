@@ -12,8 +12,11 @@ use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use std::fs;
 use std::io;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::string::{String, ToString};
+#[cfg(feature = "cache-sqlite")]
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
 
 /// Module for configuring the cache system.
 pub mod conf {
@@ -23,17 +26,73 @@ pub mod conf {
     use std::fs;
     use std::path::{Path, PathBuf};
     use std::sync::atomic::{AtomicBool, Ordering};
+    use std::time::Duration;
+
+    /// Whether the cache may be mutated, or is only read from.
+    ///
+    /// `ReadOnly` mirrors sccache's `SCCACHE_LOCAL_RW_MODE`: a prebuilt cache
+    /// directory can be seeded and reused without ever being written to (or
+    /// needing write permissions), which also lets several processes share an
+    /// immutable cache safely.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum CacheMode {
+        ReadWrite,
+        ReadOnly,
+    }
+
+    /// Selects which storage backend the cache uses.
+    ///
+    /// `Disk` keeps the default one-file-per-module layout. `Sqlite` stores
+    /// every artifact in a single SQLite database, which avoids the inode and
+    /// directory-scan costs of huge numbers of tiny files and makes bulk
+    /// eviction a simple query (the approach Deno uses for its V8 code cache).
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum CacheBackend {
+        Disk,
+        Sqlite,
+    }
+
+    /// User-facing knobs for [`init`]. Construct one with
+    /// [`CacheConfig::default`] and set only the fields you need; any option
+    /// left `None` falls back to a sensible default.
+    #[derive(Clone, Debug, Default)]
+    pub struct CacheConfig {
+        /// Whether the cache is enabled at all.
+        pub enabled: bool,
+        /// Directory to store cached modules in; defaults to the platform cache
+        /// directory when `None`.
+        pub directory: Option<PathBuf>,
+        /// zstd compression level (0 means zstd's own default).
+        pub compression_level: Option<i32>,
+        /// Maximum on-disk cache size, in bytes, before LRU eviction kicks in.
+        pub size_bytes: Option<u64>,
+        /// Whether the cache may be written to, or is read-only.
+        pub mode: Option<CacheMode>,
+        /// Endpoint of a shared/remote cache store, if any.
+        pub remote_url: Option<String>,
+        /// Which storage backend to use.
+        pub backend: Option<CacheBackend>,
+        /// Maximum age an entry may reach before it's treated as expired.
+        pub max_age: Option<Duration>,
+    }
 
     struct Config {
         pub cache_enabled: bool,
         pub cache_dir: PathBuf,
         pub compression_level: i32,
+        pub cache_size_bytes: u64,
+        pub cache_mode: CacheMode,
+        pub cache_remote_url: Option<String>,
+        pub cache_backend: CacheBackend,
+        pub cache_max_age: Option<Duration>,
     }
 
     // Private static, so only internal function can access it.
     static CONFIG: Once<Config> = Once::new();
     static INIT_CALLED: AtomicBool = AtomicBool::new(false);
     static DEFAULT_COMPRESSION_LEVEL: i32 = 0; // 0 for zstd means "use default level"
+    // Default on-disk budget, mirroring sccache's 10 GiB SCCACHE_CACHE_SIZE default.
+    static DEFAULT_CACHE_SIZE_BYTES: u64 = 10 * 1024 * 1024 * 1024;
 
     /// Returns true if and only if the cache is enabled.
     pub fn cache_enabled() -> bool {
@@ -64,9 +123,67 @@ pub mod conf {
             .compression_level
     }
 
+    /// Returns the maximum allowed on-disk cache size, in bytes.
+    ///
+    /// This budget applies to the disk backend (and the local mirror of the
+    /// remote backend); the SQLite backend enforces it with its own query.
+    ///
+    /// Panics if the cache is disabled.
+    pub fn cache_size_bytes() -> u64 {
+        CONFIG
+            .r#try()
+            .expect("Cache system must be initialized")
+            .cache_size_bytes
+    }
+
+    /// Returns the cache access mode (read-write or read-only).
+    ///
+    /// Panics if the cache is disabled.
+    pub fn cache_mode() -> CacheMode {
+        CONFIG
+            .r#try()
+            .expect("Cache system must be initialized")
+            .cache_mode
+    }
+
+    /// Returns the endpoint of the shared/remote cache store, if one was
+    /// configured. It is only used with the disk backend; when `None` there,
+    /// the local filesystem store is used.
+    ///
+    /// Panics if the cache is disabled.
+    pub fn cache_remote_url() -> Option<&'static str> {
+        CONFIG
+            .r#try()
+            .expect("Cache system must be initialized")
+            .cache_remote_url
+            .as_ref()
+            .map(String::as_str)
+    }
+
+    /// Returns the storage backend the cache should use.
+    ///
+    /// Panics if the cache is disabled.
+    pub fn cache_backend() -> CacheBackend {
+        CONFIG
+            .r#try()
+            .expect("Cache system must be initialized")
+            .cache_backend
+    }
+
+    /// Returns the maximum age cached entries may reach before being treated as
+    /// expired, if a bound was configured.
+    ///
+    /// Panics if the cache is disabled.
+    pub fn cache_max_age() -> Option<Duration> {
+        CONFIG
+            .r#try()
+            .expect("Cache system must be initialized")
+            .cache_max_age
+    }
+
     /// Initializes the cache system. Should be called exactly once,
     /// and before using the cache system. Otherwise it can panic.
-    pub fn init<P: AsRef<Path>>(enabled: bool, dir: Option<P>, compression_level: Option<i32>) {
+    pub fn init(config: CacheConfig) {
         INIT_CALLED
             .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
             .expect("Cache system init must be called at most once");
@@ -74,16 +191,19 @@ pub mod conf {
             CONFIG.r#try().is_none(),
             "Cache system init must be called before using the system."
         );
-        let conf = CONFIG.call_once(|| {
-            Config::new(
-                enabled,
-                dir,
-                compression_level.unwrap_or(DEFAULT_COMPRESSION_LEVEL),
-            )
-        });
+        let conf = CONFIG.call_once(|| Config::new(config));
         debug!(
-            "Cache init(): enabled={}, cache-dir={:?}, compression-level={}",
-            conf.cache_enabled, conf.cache_dir, conf.compression_level,
+            "Cache init(): enabled={}, cache-dir={:?}, compression-level={}, \
+             cache-size-bytes={}, cache-mode={:?}, cache-remote-url={:?}, \
+             cache-backend={:?}, cache-max-age={:?}",
+            conf.cache_enabled,
+            conf.cache_dir,
+            conf.compression_level,
+            conf.cache_size_bytes,
+            conf.cache_mode,
+            conf.cache_remote_url,
+            conf.cache_backend,
+            conf.cache_max_age,
         );
     }
 
@@ -93,17 +213,20 @@ pub mod conf {
                 cache_enabled: false,
                 cache_dir: PathBuf::new(),
                 compression_level: DEFAULT_COMPRESSION_LEVEL,
+                cache_size_bytes: DEFAULT_CACHE_SIZE_BYTES,
+                cache_mode: CacheMode::ReadWrite,
+                cache_remote_url: None,
+                cache_backend: CacheBackend::Disk,
+                cache_max_age: None,
             }
         }
 
-        pub fn new<P: AsRef<Path>>(enabled: bool, dir: Option<P>, compression_level: i32) -> Self {
-            if enabled {
-                match dir {
-                    Some(dir) => Self::new_step2(dir.as_ref(), compression_level),
+        pub fn new(config: CacheConfig) -> Self {
+            if config.enabled {
+                match &config.directory {
+                    Some(dir) => Self::new_step2(dir, &config),
                     None => match ProjectDirs::from("", "CraneStation", "wasmtime") {
-                        Some(proj_dirs) => {
-                            Self::new_step2(proj_dirs.cache_dir(), compression_level)
-                        }
+                        Some(proj_dirs) => Self::new_step2(proj_dirs.cache_dir(), &config),
                         None => {
                             warn!("Cache directory not specified and failed to find the default. Disabling cache.");
                             Self::new_cache_disabled()
@@ -115,7 +238,7 @@ pub mod conf {
             }
         }
 
-        fn new_step2(dir: &Path, compression_level: i32) -> Self {
+        fn new_step2(dir: &Path, config: &CacheConfig) -> Self {
             // On Windows, if we want long paths, we need '\\?\' prefix, but it doesn't work
             // with relative paths. One way to get absolute path (the only one?) is to use
             // fs::canonicalize, but it requires that given path exists. The extra advantage
@@ -125,7 +248,14 @@ pub mod conf {
                     Ok(p) => Self {
                         cache_enabled: true,
                         cache_dir: p,
-                        compression_level,
+                        compression_level: config
+                            .compression_level
+                            .unwrap_or(DEFAULT_COMPRESSION_LEVEL),
+                        cache_size_bytes: config.size_bytes.unwrap_or(DEFAULT_CACHE_SIZE_BYTES),
+                        cache_mode: config.mode.unwrap_or(CacheMode::ReadWrite),
+                        cache_remote_url: config.remote_url.clone(),
+                        cache_backend: config.backend.unwrap_or(CacheBackend::Disk),
+                        cache_max_age: config.max_age,
                     },
                     Err(err) => {
                         warn!(
@@ -174,8 +304,466 @@ lazy_static! {
     };
 }
 
+/// Backing storage for compiled-module artifacts.
+///
+/// Keys are the relative `triple/compiler_dir/mod-hash` path that used to name
+/// the on-disk file; a backend is free to interpret that as a filesystem path,
+/// an object-store key, etc. Implementations must be resilient to missing
+/// entries and transient errors, reporting them as a miss (`get`) or a failure
+/// to persist (`put`) rather than panicking.
+pub trait CacheStore: Send + Sync {
+    /// Fetch the raw (compressed) bytes previously stored under `key`, or
+    /// `None` on a miss or any error.
+    fn get(&self, key: &Path) -> Option<Vec<u8>>;
+
+    /// Persist `bytes` under `key`, returning `true` on success.
+    fn put(&self, key: &Path, bytes: &[u8]) -> bool;
+
+    /// Remove the entry stored under `key`, if any. Used to discard stale
+    /// entries so the cache can self-heal; a missing entry is not an error.
+    fn delete(&self, key: &Path);
+}
+
+/// The default backend: one file per module under the local cache directory.
+pub struct DiskCacheStore;
+
+impl CacheStore for DiskCacheStore {
+    fn get(&self, key: &Path) -> Option<Vec<u8>> {
+        let path = conf::cache_directory().join(key);
+        fs::read(path).ok()
+    }
+
+    fn put(&self, key: &Path, bytes: &[u8]) -> bool {
+        let path = conf::cache_directory().join(key);
+
+        // Optimize syscalls: first, try writing to disk. It should succeed in most cases.
+        // Otherwise, try creating the cache directory and retry writing to the file.
+        let err = match fs::write(&path, bytes) {
+            Ok(()) => return true,
+            Err(err) => err,
+        };
+        debug!(
+            "Attempting to create the cache directory, because \
+             failed to write cached code to disk, path: {}, message: {}",
+            path.display(),
+            err,
+        );
+
+        let cache_dir = path.parent().unwrap();
+        if let Err(err) = fs::create_dir_all(cache_dir) {
+            warn!(
+                "Failed to create cache directory, path: {}, message: {}",
+                cache_dir.display(),
+                err
+            );
+            return false;
+        }
+
+        let err = match fs::write(&path, bytes) {
+            Ok(()) => return true,
+            Err(err) => err,
+        };
+        warn!(
+            "Failed to write cached code to disk, path: {}, message: {}",
+            path.display(),
+            err
+        );
+        if let Err(err) = fs::remove_file(&path) {
+            if err.kind() != io::ErrorKind::NotFound {
+                warn!(
+                    "Failed to cleanup invalid cache, path: {}, message: {}",
+                    path.display(),
+                    err
+                );
+            }
+        }
+        false
+    }
+
+    fn delete(&self, key: &Path) {
+        let path = conf::cache_directory().join(key);
+        if let Err(err) = fs::remove_file(&path) {
+            if err.kind() != io::ErrorKind::NotFound {
+                warn!(
+                    "Failed to delete stale cache entry, path: {}, message: {}",
+                    path.display(),
+                    err
+                );
+            }
+        }
+    }
+}
+
+/// A shared cache backed by an HTTP/object-store endpoint, so that compiled
+/// artifacts can be reused across machines (as sccache does with its S3/remote
+/// backends). A local [`DiskCacheStore`] is kept alongside it: a remote hit is
+/// mirrored locally, and a remote miss falls back to the local copy, so a cold
+/// runner can still benefit from artifacts compiled elsewhere.
+///
+/// Gated behind the `cache-remote` feature so that the HTTP/TLS dependency
+/// stack is only compiled in when this backend is actually used.
+#[cfg(feature = "cache-remote")]
+pub struct RemoteCacheStore {
+    base_url: String,
+    local: DiskCacheStore,
+    client: reqwest::blocking::Client,
+}
+
+#[cfg(feature = "cache-remote")]
+impl RemoteCacheStore {
+    fn new(base_url: String) -> Self {
+        // Trim a trailing slash so joining object keys is unambiguous.
+        let base_url = base_url.trim_end_matches('/').to_string();
+        Self {
+            base_url,
+            local: DiskCacheStore,
+            client: reqwest::blocking::Client::new(),
+        }
+    }
+
+    /// Turn a relative cache key into the object's absolute URL, using forward
+    /// slashes regardless of the host platform's path separator.
+    fn object_url(&self, key: &Path) -> String {
+        let key = key.to_string_lossy().replace('\\', "/");
+        format!("{}/{}", self.base_url, key)
+    }
+}
+
+#[cfg(feature = "cache-remote")]
+impl CacheStore for RemoteCacheStore {
+    fn get(&self, key: &Path) -> Option<Vec<u8>> {
+        let url = self.object_url(key);
+        match self.client.get(&url).send() {
+            Ok(resp) => {
+                if resp.status().is_success() {
+                    match resp.bytes() {
+                        Ok(bytes) => {
+                            let bytes = bytes.to_vec();
+                            // Mirror the artifact locally for subsequent lookups,
+                            // unless the cache is read-only: a seeded/immutable
+                            // shared directory must never be written to on a read.
+                            if conf::cache_mode() == conf::CacheMode::ReadWrite {
+                                self.local.put(key, &bytes);
+                            }
+                            return Some(bytes);
+                        }
+                        Err(err) => warn!("Failed to read remote cache body {}: {}", url, err),
+                    }
+                } else {
+                    trace!("Remote cache miss ({}) for {}", resp.status(), url);
+                }
+            }
+            Err(err) => warn!("Failed to query remote cache {}: {}", url, err),
+        }
+        // Fall back to any locally mirrored copy.
+        self.local.get(key)
+    }
+
+    fn put(&self, key: &Path, bytes: &[u8]) -> bool {
+        // Always keep a local copy; additionally try to populate the remote.
+        let local_ok = self.local.put(key, bytes);
+        let url = self.object_url(key);
+        match self.client.put(&url).body(bytes.to_vec()).send() {
+            Ok(resp) if resp.status().is_success() => true,
+            Ok(resp) => {
+                warn!("Failed to populate remote cache {}: status {}", url, resp.status());
+                local_ok
+            }
+            Err(err) => {
+                warn!("Failed to populate remote cache {}: {}", url, err);
+                local_ok
+            }
+        }
+    }
+
+    fn delete(&self, key: &Path) {
+        self.local.delete(key);
+        let url = self.object_url(key);
+        if let Err(err) = self.client.delete(&url).send() {
+            warn!("Failed to delete remote cache entry {}: {}", url, err);
+        }
+    }
+}
+
+// Bumped whenever the column layout of the SQLite store changes so that old
+// databases are ignored rather than misread.
+#[cfg(feature = "cache-sqlite")]
+static SQLITE_SCHEMA_VERSION: i64 = 1;
+
+/// A backend that keeps every artifact in a single SQLite database rather than
+/// one file per module, avoiding the inode and directory-scan costs of the
+/// filesystem store and making bulk eviction a simple query. Rows are keyed by
+/// the same `triple/compiler_dir/mod-hash` key, with the triple and compiler
+/// split out as discriminator columns, and carry `created_at`/`last_used`
+/// timestamps and a schema version.
+///
+/// Gated behind the `cache-sqlite` feature so that libsqlite is only linked in
+/// when this backend is actually used.
+#[cfg(feature = "cache-sqlite")]
+pub struct SqliteCacheStore {
+    conn: Mutex<rusqlite::Connection>,
+}
+
+#[cfg(feature = "cache-sqlite")]
+impl SqliteCacheStore {
+    fn new() -> Option<Self> {
+        let path = conf::cache_directory().join("cache.sqlite");
+        // In read-only mode open the database read-only so a seeded/immutable
+        // cache on a read-only mount can be shared safely, without creating the
+        // `-wal`/`-journal` side files. Only the read-write path may create or
+        // migrate the schema.
+        if conf::cache_mode() == conf::CacheMode::ReadOnly {
+            let conn = rusqlite::Connection::open_with_flags(
+                &path,
+                rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY,
+            )
+            .map_err(|err| {
+                warn!("Failed to open SQLite cache at {}: {}", path.display(), err)
+            })
+            .ok()?;
+            return Some(Self {
+                conn: Mutex::new(conn),
+            });
+        }
+
+        let conn = rusqlite::Connection::open(&path)
+            .map_err(|err| warn!("Failed to open SQLite cache at {}: {}", path.display(), err))
+            .ok()?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS modules (
+                 key            TEXT PRIMARY KEY,
+                 triple         TEXT NOT NULL,
+                 compiler       TEXT NOT NULL,
+                 schema_version INTEGER NOT NULL,
+                 created_at     INTEGER NOT NULL,
+                 last_used      INTEGER NOT NULL,
+                 blob           BLOB NOT NULL
+             );
+             CREATE INDEX IF NOT EXISTS idx_modules_last_used
+                 ON modules (last_used);",
+        )
+        .map_err(|err| warn!("Failed to initialize SQLite cache schema: {}", err))
+        .ok()?;
+        Some(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// Split the cache key into its triple and compiler discriminator columns.
+    fn key_discriminators(key: &Path) -> (String, String) {
+        let mut comps = key
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().into_owned());
+        let triple = comps.next().unwrap_or_default();
+        let compiler = comps.next().unwrap_or_default();
+        (triple, compiler)
+    }
+
+    /// Enforce the configured size budget with a single bulk eviction query:
+    /// drop least-recently-used rows until the total blob size falls below the
+    /// low-water mark. Cheap thanks to the `last_used` index, so there's no need
+    /// to gate it behind a sentinel like the disk GC.
+    fn enforce_budget(conn: &rusqlite::Connection) {
+        let budget = conf::cache_size_bytes();
+        let total: i64 = match conn.query_row(
+            "SELECT COALESCE(SUM(LENGTH(blob)), 0) FROM modules",
+            [],
+            |row| row.get(0),
+        ) {
+            Ok(total) => total,
+            Err(err) => {
+                warn!("Failed to measure SQLite cache size: {}", err);
+                return;
+            }
+        };
+        if (total as u64) <= budget {
+            return;
+        }
+        // Keep the most-recently-used rows whose cumulative size stays within
+        // the low-water mark; delete everything older.
+        let low_water = low_water_mark(budget) as i64;
+        if let Err(err) = conn.execute(
+            "DELETE FROM modules WHERE key IN (
+                 SELECT key FROM (
+                     SELECT key,
+                            SUM(LENGTH(blob)) OVER (
+                                ORDER BY last_used DESC
+                                ROWS UNBOUNDED PRECEDING
+                            ) AS running
+                     FROM modules
+                 ) WHERE running > ?1
+             )",
+            rusqlite::params![low_water],
+        ) {
+            warn!("Failed to evict from SQLite cache: {}", err);
+        }
+    }
+}
+
+#[cfg(feature = "cache-sqlite")]
+impl CacheStore for SqliteCacheStore {
+    fn get(&self, key: &Path) -> Option<Vec<u8>> {
+        let key_str = key.to_string_lossy();
+        let conn = self.conn.lock().ok()?;
+        let blob: Vec<u8> = conn
+            .query_row(
+                "SELECT blob FROM modules WHERE key = ?1 AND schema_version = ?2",
+                rusqlite::params![key_str, SQLITE_SCHEMA_VERSION],
+                |row| row.get(0),
+            )
+            .ok()?;
+        // Record the access for future eviction decisions; a failure here is
+        // not worth failing the lookup over. Skip it entirely in read-only
+        // mode, where the database (and its mount) must never be written to.
+        if conf::cache_mode() == conf::CacheMode::ReadWrite {
+            let _ = conn.execute(
+                "UPDATE modules SET last_used = ?1 WHERE key = ?2",
+                rusqlite::params![now_unix(), key_str],
+            );
+        }
+        Some(blob)
+    }
+
+    fn put(&self, key: &Path, bytes: &[u8]) -> bool {
+        let key_str = key.to_string_lossy();
+        let (triple, compiler) = Self::key_discriminators(key);
+        let now = now_unix();
+        let conn = match self.conn.lock() {
+            Ok(conn) => conn,
+            Err(_) => return false,
+        };
+        match conn.execute(
+            "INSERT INTO modules
+                 (key, triple, compiler, schema_version, created_at, last_used, blob)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?5, ?6)
+             ON CONFLICT(key) DO UPDATE SET
+                 schema_version = excluded.schema_version,
+                 last_used = excluded.last_used,
+                 blob = excluded.blob",
+            rusqlite::params![key_str, triple, compiler, SQLITE_SCHEMA_VERSION, now, bytes],
+        ) {
+            Ok(_) => {
+                Self::enforce_budget(&conn);
+                true
+            }
+            Err(err) => {
+                warn!("Failed to write module to SQLite cache: {}", err);
+                false
+            }
+        }
+    }
+
+    fn delete(&self, key: &Path) {
+        let key_str = key.to_string_lossy();
+        if let Ok(conn) = self.conn.lock() {
+            if let Err(err) =
+                conn.execute("DELETE FROM modules WHERE key = ?1", rusqlite::params![key_str])
+            {
+                warn!("Failed to delete stale SQLite cache entry: {}", err);
+            }
+        }
+    }
+}
+
+/// Seconds since the Unix epoch, clamped to zero if the clock predates it.
+fn now_unix() -> i64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+lazy_static! {
+    /// The process-wide cache store, chosen from the configuration the first
+    /// time the cache is used (after `conf::init`).
+    static ref CACHE_STORE: Box<dyn CacheStore> = match conf::cache_backend() {
+        #[cfg(feature = "cache-sqlite")]
+        conf::CacheBackend::Sqlite => match SqliteCacheStore::new() {
+            Some(store) => Box::new(store),
+            None => {
+                warn!("Falling back to the filesystem cache store");
+                Box::new(DiskCacheStore)
+            }
+        },
+        #[cfg(not(feature = "cache-sqlite"))]
+        conf::CacheBackend::Sqlite => {
+            warn!(
+                "The SQLite cache backend was selected but wasmtime was built \
+                 without the `cache-sqlite` feature; using the local disk cache"
+            );
+            Box::new(DiskCacheStore)
+        }
+        conf::CacheBackend::Disk => match conf::cache_remote_url() {
+            #[cfg(feature = "cache-remote")]
+            Some(url) => Box::new(RemoteCacheStore::new(url.to_string())),
+            #[cfg(not(feature = "cache-remote"))]
+            Some(_) => {
+                warn!(
+                    "A remote cache URL was configured but wasmtime was built \
+                     without the `cache-remote` feature; using the local disk cache"
+                );
+                Box::new(DiskCacheStore)
+            }
+            None => Box::new(DiskCacheStore),
+        },
+    };
+}
+
+/// Returns the process-wide cache store selected by the configuration.
+fn cache_store() -> &'static dyn CacheStore {
+    &**CACHE_STORE
+}
+
+/// Discard an entry found to be stale during a read (wrong format or expired),
+/// but only when the cache is writable. In read-only mode a seeded cache must
+/// never be mutated (nor should we spam warnings trying to unlink from a
+/// permissioned directory), so the caller simply treats it as a miss.
+fn discard_stale_entry(key: &Path) {
+    if conf::cache_mode() == conf::CacheMode::ReadWrite {
+        cache_store().delete(key);
+    }
+}
+
+// A fixed envelope prepended to every cached payload. Because `ModuleCacheData`
+// is serialized straight to bincode, any change to its layout (or to the
+// serialization of downstream cranelift types) would otherwise only surface as
+// a deserialize failure. The header lets `get_data` reject a stale entry up
+// front and discard it, so wasmtime upgrades that change the cache layout are
+// safe and self-cleaning rather than relying on the compiler-version directory
+// segment (which doesn't change for local `debug_assertions` rebuilds).
+static CACHE_MAGIC: &[u8; 4] = b"WTMC";
+// Bumped whenever the serialized schema changes. Started at 1 (magic + version
+// only); 2 added the creation timestamp to the header for TTL tracking.
+static CURRENT_VERSION: u32 = 2;
+// magic (4 bytes) + version (4 bytes) + creation time (8 bytes), little-endian.
+static HEADER_LEN: usize = 16;
+
+/// Prepend the cache-format header to `buf`, stamping it with the current time.
+fn write_header(buf: &mut Vec<u8>) {
+    buf.extend_from_slice(CACHE_MAGIC);
+    buf.extend_from_slice(&CURRENT_VERSION.to_le_bytes());
+    buf.extend_from_slice(&(now_unix() as u64).to_le_bytes());
+}
+
+/// Validate the header at the front of `bytes`, returning the offset of the
+/// compressed payload and the entry's creation time when the magic and version
+/// match, or `None` otherwise.
+fn check_header(bytes: &[u8]) -> Option<(usize, SystemTime)> {
+    if bytes.len() < HEADER_LEN || &bytes[0..4] != CACHE_MAGIC {
+        return None;
+    }
+    let version = u32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]);
+    if version != CURRENT_VERSION {
+        return None;
+    }
+    let mut secs = [0u8; 8];
+    secs.copy_from_slice(&bytes[8..16]);
+    let created_at = SystemTime::UNIX_EPOCH + Duration::from_secs(u64::from_le_bytes(secs));
+    Some((HEADER_LEN, created_at))
+}
+
 pub struct ModuleCacheEntry {
-    mod_cache_path: Option<PathBuf>,
+    cache_key: Option<PathBuf>,
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
@@ -205,7 +793,7 @@ impl ModuleCacheEntry {
         compiler_name: &str,
         generate_debug_info: bool,
     ) -> Self {
-        let mod_cache_path = if conf::cache_enabled() {
+        let cache_key = if conf::cache_enabled() {
             let hash = Sha256Hasher::digest(module, function_body_inputs);
             let compiler_dir = if cfg!(debug_assertions) {
                 format!(
@@ -226,9 +814,10 @@ impl ModuleCacheEntry {
                 mod_hash = base64::encode_config(&hash, base64::URL_SAFE_NO_PAD), // standard encoding uses '/' which can't be used for filename
                 mod_dbg = if generate_debug_info { ".d" } else { "" },
             );
+            // The relative object key: the same layout that used to name the
+            // on-disk file, now interpreted by the configured `CacheStore`.
             Some(
-                conf::cache_directory()
-                    .join(isa.triple().to_string())
+                PathBuf::from(isa.triple().to_string())
                     .join(compiler_dir)
                     .join(mod_filename),
             )
@@ -236,19 +825,48 @@ impl ModuleCacheEntry {
             None
         };
 
-        Self { mod_cache_path }
+        Self { cache_key }
     }
 
     pub fn get_data(&self) -> Option<ModuleCacheData> {
-        let path = self.mod_cache_path.as_ref()?;
-        trace!("get_data() for path: {}", path.display());
-        let compressed_cache_bytes = fs::read(path).ok()?;
-        let cache_bytes = zstd::decode_all(&compressed_cache_bytes[..])
+        self.get_data_and_age().map(|(data, _age)| data)
+    }
+
+    /// Like [`get_data`](Self::get_data), but also reports how old the served
+    /// entry is, so tooling can surface "served from N-day-old cache"
+    /// diagnostics.
+    pub fn get_data_and_age(&self) -> Option<(ModuleCacheData, Duration)> {
+        let key = self.cache_key.as_ref()?;
+        trace!("get_data() for key: {}", key.display());
+        let raw = cache_store().get(key)?;
+        let (payload_offset, created_at) = match check_header(&raw) {
+            Some(header) => header,
+            None => {
+                // Stale or foreign format: treat as a clean miss and discard it.
+                debug!("Discarding cache entry with unknown format: {}", key.display());
+                discard_stale_entry(key);
+                return None;
+            }
+        };
+        // A clock that ran backwards since the entry was written yields a zero
+        // age rather than a spurious expiration.
+        let age = SystemTime::now()
+            .duration_since(created_at)
+            .unwrap_or_default();
+        if let Some(max_age) = conf::cache_max_age() {
+            if age > max_age {
+                debug!("Discarding cache entry older than max age: {}", key.display());
+                discard_stale_entry(key);
+                return None;
+            }
+        }
+        let cache_bytes = zstd::decode_all(&raw[payload_offset..])
             .map_err(|err| warn!("Failed to decompress cached code: {}", err))
             .ok()?;
-        bincode::deserialize(&cache_bytes[..])
+        let data = bincode::deserialize(&cache_bytes[..])
             .map_err(|err| warn!("Failed to deserialize cached code: {}", err))
-            .ok()
+            .ok()?;
+        Some((data, age))
     }
 
     pub fn update_data(&self, data: &ModuleCacheData) {
@@ -256,8 +874,14 @@ impl ModuleCacheEntry {
     }
 
     fn update_data_impl(&self, data: &ModuleCacheData) -> Option<()> {
-        let path = self.mod_cache_path.as_ref()?;
-        trace!("update_data() for path: {}", path.display());
+        let key = self.cache_key.as_ref()?;
+        // In read-only mode the cache is treated as immutable: skip all
+        // serialization and writes, but `get_data` still reads normally.
+        if conf::cache_mode() == conf::CacheMode::ReadOnly {
+            trace!("update_data() skipped (read-only cache): {}", key.display());
+            return None;
+        }
+        trace!("update_data() for key: {}", key.display());
         let serialized_data = bincode::serialize(&data)
             .map_err(|err| warn!("Failed to serialize cached code: {}", err))
             .ok()?;
@@ -265,44 +889,148 @@ impl ModuleCacheEntry {
             .map_err(|err| warn!("Failed to compress cached code: {}", err))
             .ok()?;
 
-        // Optimize syscalls: first, try writing to disk. It should succeed in most cases.
-        // Otherwise, try creating the cache directory and retry writing to the file.
-        let err = fs::write(path, &compressed_data).err()?; // return on success
-        debug!(
-            "Attempting to create the cache directory, because \
-             failed to write cached code to disk, path: {}, message: {}",
-            path.display(),
-            err,
-        );
+        // Wrap the compressed payload in the versioned envelope before storing.
+        let mut payload = Vec::with_capacity(HEADER_LEN + compressed_data.len());
+        write_header(&mut payload);
+        payload.extend_from_slice(&compressed_data);
 
-        let cache_dir = path.parent().unwrap();
-        fs::create_dir_all(cache_dir)
-            .map_err(|err| {
-                warn!(
-                    "Failed to create cache directory, path: {}, message: {}",
-                    cache_dir.display(),
-                    err
-                )
-            })
-            .ok()?;
+        if cache_store().put(key, &payload) {
+            maybe_collect_garbage();
+            Some(())
+        } else {
+            None
+        }
+    }
+}
 
-        let err = fs::write(path, &compressed_data).err()?;
-        warn!(
-            "Failed to write cached code to disk, path: {}, message: {}",
-            path.display(),
-            err
-        );
-        fs::remove_file(path)
-            .map_err(|err| {
-                if err.kind() != io::ErrorKind::NotFound {
-                    warn!(
-                        "Failed to cleanup invalid cache, path: {}, message: {}",
-                        path.display(),
-                        err
-                    );
-                }
-            })
-            .ok()
+// Name of the sentinel file, stored at the root of the cache directory, whose
+// mtime records when garbage collection last ran. Scanning the whole tree on
+// every compilation would be wasteful, so we only pay for it occasionally.
+static GC_SENTINEL_FILENAME: &str = ".last-gc";
+// Minimum interval between two garbage-collection scans.
+static GC_INTERVAL: Duration = Duration::from_secs(60 * 60);
+// Percentage of the budget we evict down to once the limit is exceeded, so we
+// don't run a full scan again on the very next compilation.
+static GC_LOW_WATER_FRACTION: u64 = 80;
+
+/// The size, in bytes, to evict down to once the budget is exceeded: a fixed
+/// fraction of the budget below the limit. Dividing before multiplying keeps
+/// the intermediate product from overflowing on large budgets.
+fn low_water_mark(budget: u64) -> u64 {
+    budget / 100 * GC_LOW_WATER_FRACTION
+}
+
+/// A single cached module file discovered while walking the cache directory.
+struct CacheFile {
+    path: PathBuf,
+    size: u64,
+    // Last-access time, falling back to the modification time where the
+    // platform doesn't expose atime. Older entries are evicted first.
+    atime: SystemTime,
+}
+
+/// Occasionally enforce the configured cache-size budget.
+///
+/// This walks the one-file-per-module tree, so it only applies to the disk
+/// backend, including the local mirror kept by the remote store; the SQLite
+/// backend enforces its own size bound and would otherwise pay for a fruitless
+/// directory scan after every `put`. The remote store itself is never pruned.
+/// The scan is gated behind a sentinel file so that only the rare
+/// compilation that finds the sentinel stale actually walks the tree; everyone
+/// else returns immediately. Any I/O error simply leaves the cache untouched.
+fn maybe_collect_garbage() {
+    if conf::cache_backend() != conf::CacheBackend::Disk {
+        return;
+    }
+    let cache_dir = conf::cache_directory();
+    let sentinel = cache_dir.join(GC_SENTINEL_FILENAME);
+    if !gc_is_due(&sentinel) {
+        return;
+    }
+    // Touch the sentinel up-front so that concurrent processes don't all decide
+    // the scan is due at once.
+    let _ = fs::write(&sentinel, b"");
+
+    let budget = conf::cache_size_bytes();
+    let mut files = Vec::new();
+    let mut total = 0u64;
+    collect_cache_files(cache_dir, &mut files, &mut total);
+
+    if total <= budget {
+        return;
+    }
+
+    // Evict least-recently-used entries until we drop below the low-water mark.
+    let low_water = low_water_mark(budget);
+    files.sort_by_key(|f| f.atime);
+    for file in files {
+        if total <= low_water {
+            break;
+        }
+        match fs::remove_file(&file.path) {
+            Ok(()) => {
+                debug!("Cache GC evicted {}", file.path.display());
+                total = total.saturating_sub(file.size);
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                // Another process removed it; count it as reclaimed.
+                total = total.saturating_sub(file.size);
+            }
+            Err(err) => warn!(
+                "Cache GC failed to evict {}: {}",
+                file.path.display(),
+                err
+            ),
+        }
+    }
+}
+
+/// Returns true if enough time has elapsed since the last scan (or the sentinel
+/// is missing/unreadable) that another garbage-collection pass is warranted.
+fn gc_is_due(sentinel: &Path) -> bool {
+    match fs::metadata(sentinel).and_then(|m| m.modified()) {
+        Ok(last) => match SystemTime::now().duration_since(last) {
+            Ok(elapsed) => elapsed >= GC_INTERVAL,
+            // Clock went backwards; don't scan just because of that.
+            Err(_) => false,
+        },
+        Err(_) => true,
+    }
+}
+
+/// Recursively collect every `mod-*` cache file under `dir`, accumulating the
+/// total size. Entries that vanish mid-scan (a concurrent process) are skipped
+/// rather than treated as errors.
+fn collect_cache_files(dir: &Path, files: &mut Vec<CacheFile>, total: &mut u64) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    for entry in entries.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        let metadata = match entry.metadata() {
+            Ok(metadata) => metadata,
+            // The file disappeared between listing and stat; ignore it.
+            Err(_) => continue,
+        };
+        if metadata.is_dir() {
+            collect_cache_files(&path, files, total);
+            continue;
+        }
+        let is_module = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .map_or(false, |n| n.starts_with("mod-"));
+        if !is_module {
+            continue;
+        }
+        let size = metadata.len();
+        let atime = metadata
+            .accessed()
+            .or_else(|_| metadata.modified())
+            .unwrap_or(SystemTime::UNIX_EPOCH);
+        *total += size;
+        files.push(CacheFile { path, size, atime });
     }
 }
 
@@ -0,0 +1,54 @@
+use super::*;
+
+#[test]
+fn low_water_mark_is_80_percent_of_budget() {
+    assert_eq!(low_water_mark(1000), 800);
+    // Dividing before multiplying truncates the per-100 remainder but never
+    // overflows; the result always stays at or below the budget.
+    assert_eq!(low_water_mark(1050), 800);
+    assert_eq!(low_water_mark(0), 0);
+    assert!(low_water_mark(u64::MAX) <= u64::MAX);
+    assert!(low_water_mark(10 * 1024 * 1024 * 1024) < 10 * 1024 * 1024 * 1024);
+}
+
+#[test]
+fn check_header_round_trips_write_header() {
+    let before = now_unix() as u64;
+    let mut buf = Vec::new();
+    write_header(&mut buf);
+    assert_eq!(buf.len(), HEADER_LEN);
+
+    let (offset, created_at) = check_header(&buf).expect("freshly written header is valid");
+    assert_eq!(offset, HEADER_LEN);
+    let stamped = created_at
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    assert!(stamped >= before);
+}
+
+#[test]
+fn check_header_rejects_bad_magic() {
+    let mut buf = vec![b'N', b'O', b'P', b'E'];
+    buf.extend_from_slice(&CURRENT_VERSION.to_le_bytes());
+    buf.extend_from_slice(&0u64.to_le_bytes());
+    assert!(check_header(&buf).is_none());
+}
+
+#[test]
+fn check_header_rejects_wrong_version() {
+    let mut buf = CACHE_MAGIC.to_vec();
+    buf.extend_from_slice(&(CURRENT_VERSION + 1).to_le_bytes());
+    buf.extend_from_slice(&0u64.to_le_bytes());
+    assert!(check_header(&buf).is_none());
+}
+
+#[test]
+fn check_header_rejects_truncated_input() {
+    let mut buf = CACHE_MAGIC.to_vec();
+    buf.extend_from_slice(&CURRENT_VERSION.to_le_bytes());
+    // One byte short of a full header (missing part of the timestamp).
+    buf.extend_from_slice(&[0u8; 7]);
+    assert_eq!(buf.len(), HEADER_LEN - 1);
+    assert!(check_header(&buf).is_none());
+}